@@ -18,12 +18,16 @@ use winapi::xinput::{self as xi, XINPUT_BATTERY_INFORMATION as XBatteryInfo,
                      XINPUT_GAMEPAD_DPAD_LEFT, XINPUT_GAMEPAD_DPAD_RIGHT, XINPUT_GAMEPAD_DPAD_UP,
                      XINPUT_GAMEPAD_LEFT_SHOULDER, XINPUT_GAMEPAD_LEFT_THUMB,
                      XINPUT_GAMEPAD_RIGHT_SHOULDER, XINPUT_GAMEPAD_RIGHT_THUMB,
-                     XINPUT_GAMEPAD_START, XINPUT_GAMEPAD_X, XINPUT_GAMEPAD_Y};
+                     XINPUT_GAMEPAD_START, XINPUT_GAMEPAD_X, XINPUT_GAMEPAD_Y,
+                     XINPUT_GAMEPAD_LEFT_THUMB_DEADZONE, XINPUT_GAMEPAD_RIGHT_THUMB_DEADZONE,
+                     XINPUT_GAMEPAD_TRIGGER_THRESHOLD};
 use xinput;
 
 use std::{mem, thread, u16, u32};
 use std::collections::VecDeque;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::Duration;
 
@@ -31,9 +35,32 @@ use std::time::Duration;
 const EVENT_THREAD_SLEEP_TIME: u64 = 10;
 const ITERATIONS_TO_CHECK_IF_CONNECTED: u64 = 100;
 
+// XInput exposes at most four slots; DirectInput devices are assigned ids past
+// that range so the two subsystems never collide.
+const MAX_XINPUT_PADS: usize = 4;
+
+// `XInputGetCapabilities` subtypes and capability flags. The XInput 1.4-only
+// subtypes (and the caps flags) are absent from the pinned `winapi::xinput`, so
+// we spell out the documented values here rather than depend on the crate
+// exposing them.
+const XINPUT_DEVSUBTYPE_GAMEPAD: u8 = 0x01;
+const XINPUT_DEVSUBTYPE_WHEEL: u8 = 0x02;
+const XINPUT_DEVSUBTYPE_ARCADE_STICK: u8 = 0x03;
+const XINPUT_DEVSUBTYPE_FLIGHT_STICK: u8 = 0x04;
+const XINPUT_DEVSUBTYPE_DANCE_PAD: u8 = 0x05;
+const XINPUT_DEVSUBTYPE_GUITAR: u8 = 0x06;
+const XINPUT_DEVSUBTYPE_GUITAR_ALTERNATE: u8 = 0x07;
+const XINPUT_DEVSUBTYPE_DRUM_KIT: u8 = 0x08;
+const XINPUT_DEVSUBTYPE_GUITAR_BASS: u8 = 0x0B;
+const XINPUT_DEVSUBTYPE_ARCADE_PAD: u8 = 0x13;
+
+const XINPUT_FLAG_GAMEPAD: u32 = 0x0000_0001;
+const XINPUT_CAPS_FFB_SUPPORTED: u16 = 0x0001;
+const XINPUT_CAPS_VOICE_SUPPORTED: u16 = 0x0004;
+
 #[derive(Debug)]
 pub struct Gilrs {
-    gamepads: [gamepad::Gamepad; 4],
+    gamepads: Vec<gamepad::Gamepad>,
     rx: Receiver<RawEvent>,
     not_observed: gamepad::Gamepad,
     additional_events: VecDeque<RawEvent>,
@@ -41,19 +68,36 @@ pub struct Gilrs {
 
 impl Gilrs {
     pub(crate) fn new() -> Result<Self, PlatformError> {
-        let gamepads = [
+        Self::with_poll_interval(Duration::from_millis(EVENT_THREAD_SLEEP_TIME))
+    }
+
+    /// Construct a `Gilrs` whose event thread wakes at `poll_interval` instead of
+    /// the default 10 ms. A shorter interval lowers input latency at the cost of
+    /// CPU; a longer one is friendlier to battery-backed machines. When
+    /// `Windows.Gaming.Input` is available (see [`wgi`]), an added or removed
+    /// controller makes the XInput slots rescan on the next tick instead of
+    /// waiting for the periodic rescan; DirectInput devices are enumerated only
+    /// here, at construction.
+    pub(crate) fn with_poll_interval(poll_interval: Duration) -> Result<Self, PlatformError> {
+        let mut gamepads = vec![
             gamepad_new(0),
             gamepad_new(1),
             gamepad_new(2),
             gamepad_new(3),
         ];
 
-        let connected = [
-            gamepads[0].is_connected(),
-            gamepads[1].is_connected(),
-            gamepads[2].is_connected(),
-            gamepads[3].is_connected(),
-        ];
+        // DirectInput pads occupy the ids right after the XInput slots. They are
+        // enumerated once here so applications see them (and their uuid/name)
+        // immediately; the event thread re-acquires the live devices for polling.
+        let di_pads = dinput::enumerate();
+        for pad in &di_pads {
+            gamepads.push(gamepad::Gamepad::from_inner_status(
+                Gamepad::from_dinput(pad),
+                Status::Connected,
+            ));
+        }
+
+        let connected: Vec<bool> = gamepads.iter().map(|g| g.is_connected()).collect();
 
         let additional_events = connected
             .iter()
@@ -64,7 +108,7 @@ impl Gilrs {
 
         unsafe { xinput::XInputEnable(1) };
         let (tx, rx) = mpsc::channel();
-        Self::spawn_thread(tx, connected);
+        Self::spawn_thread(tx, connected, di_pads, poll_interval);
 
         Ok(Gilrs {
             gamepads,
@@ -87,23 +131,59 @@ impl Gilrs {
     }
 
     pub fn gamepad_mut(&mut self, id: usize) -> &mut gamepad::Gamepad {
-        self.gamepads.get_mut(id).unwrap_or(&mut self.not_observed)
+        if id < self.gamepads.len() {
+            &mut self.gamepads[id]
+        } else {
+            &mut self.not_observed
+        }
     }
 
     pub fn last_gamepad_hint(&self) -> usize {
         self.gamepads.len()
     }
 
-    fn spawn_thread(tx: Sender<RawEvent>, connected: [bool; 4]) {
+    fn spawn_thread(
+        tx: Sender<RawEvent>,
+        connected: Vec<bool>,
+        di_infos: Vec<dinput::DiDeviceInfo>,
+        poll_interval: Duration,
+    ) {
         thread::spawn(move || unsafe {
-            let mut prev_state = mem::zeroed::<XState>();
+            // One `prev_state` per slot. A single shared sample aliases one pad's
+            // axes onto another, which would make the derived
+            // `StickDirectionChanged` edges fire spuriously once more than one
+            // controller is active.
+            let mut prev_state: [XState; MAX_XINPUT_PADS] = mem::zeroed();
             let mut state = mem::zeroed::<XState>();
             let mut connected = connected;
             let mut counter = 0;
+            // Per-slot "is the trigger held down" latch, so we only emit a digital
+            // button event when a trigger crosses the threshold.
+            let mut triggers = [TriggerState::default(); MAX_XINPUT_PADS];
+
+            // DirectInput devices live in the ids past the XInput slots. Their
+            // live COM handles can only be used on the thread that created them,
+            // so they are acquired here rather than shared from `new`.
+            let mut di_pads = dinput::acquire(di_infos);
+
+            // When Windows.Gaming.Input is available it flips this flag the moment a
+            // controller is added or removed, so we rescan that tick instead of
+            // waiting out the `ITERATIONS_TO_CHECK_IF_CONNECTED` cadence. When it is
+            // not available the flag never changes and we fall back to periodic
+            // rescans. `_wgi` keeps the registration alive for the thread's life.
+            let rescan = Arc::new(AtomicBool::new(false));
+            let _wgi = wgi::register(rescan.clone());
+
+            // Last power state observed per slot, so we only emit a
+            // `PowerInfoChanged` when it actually transitions.
+            let mut battery = [None::<PowerInfo>; MAX_XINPUT_PADS];
 
             loop {
-                for id in 0..4 {
+                let force_rescan = rescan.swap(false, Ordering::Relaxed);
+
+                for id in 0..MAX_XINPUT_PADS {
                     if *connected.get_unchecked(id)
+                        || force_rescan
                         || counter % ITERATIONS_TO_CHECK_IF_CONNECTED == 0
                     {
                         let val = xinput::XInputGetState(id as u32, &mut state);
@@ -114,9 +194,16 @@ impl Gilrs {
                                 let _ = tx.send(RawEvent::new(id, RawEventType::Connected));
                             }
 
-                            if state.dwPacketNumber != prev_state.dwPacketNumber {
-                                Self::compare_state(id, &state.Gamepad, &prev_state.Gamepad, &tx);
-                                prev_state = state;
+                            let prev = prev_state.get_unchecked_mut(id);
+                            if state.dwPacketNumber != prev.dwPacketNumber {
+                                Self::compare_state(
+                                    id,
+                                    &state.Gamepad,
+                                    &prev.Gamepad,
+                                    triggers.get_unchecked_mut(id),
+                                    &tx,
+                                );
+                                *prev = state;
                             }
                         } else if val == ERROR_DEVICE_NOT_CONNECTED && *connected.get_unchecked(id)
                         {
@@ -126,13 +213,51 @@ impl Gilrs {
                     }
                 }
 
+                // Sample battery state on the slow cadence and report transitions,
+                // so UIs can show a "controller battery low" indicator without
+                // polling `power_info` themselves.
+                if counter % ITERATIONS_TO_CHECK_IF_CONNECTED == 0 {
+                    for id in 0..MAX_XINPUT_PADS {
+                        if !connected.get_unchecked(id) {
+                            continue;
+                        }
+
+                        let mut binfo = mem::zeroed::<XBatteryInfo>();
+                        if xinput::XInputGetBatteryInformation(
+                            id as u32,
+                            xi::BATTERY_DEVTYPE_GAMEPAD,
+                            &mut binfo,
+                        ) == ERROR_SUCCESS
+                        {
+                            let info = power_info_from_battery(&binfo);
+                            if *battery.get_unchecked(id) != Some(info) {
+                                *battery.get_unchecked_mut(id) = Some(info);
+                                let _ = tx.send(RawEvent::new(
+                                    id,
+                                    RawEventType::PowerInfoChanged(info),
+                                ));
+                            }
+                        }
+                    }
+                }
+
+                for pad in di_pads.iter_mut() {
+                    pad.poll(&tx);
+                }
+
                 counter = counter.wrapping_add(1);
-                thread::sleep(Duration::from_millis(EVENT_THREAD_SLEEP_TIME));
+                thread::sleep(poll_interval);
             }
         });
     }
 
-    fn compare_state(id: usize, g: &XGamepad, pg: &XGamepad, tx: &Sender<RawEvent>) {
+    fn compare_state(
+        id: usize,
+        g: &XGamepad,
+        pg: &XGamepad,
+        trig: &mut TriggerState,
+        tx: &Sender<RawEvent>,
+    ) {
         if g.bLeftTrigger != pg.bLeftTrigger {
             let _ = tx.send(RawEvent::new(
                 id,
@@ -145,6 +270,34 @@ impl Gilrs {
                 RawEventType::AxisValueChanged(g.bRightTrigger as i32, native_ev_codes::AXIS_RT2),
             ));
         }
+        // Treat the analog triggers as digital buttons too, so games get the same
+        // LT2/RT2 presses the other backends deliver. The latch in `trig` reports
+        // only the crossings of `XINPUT_GAMEPAD_TRIGGER_THRESHOLD`.
+        let threshold = XINPUT_GAMEPAD_TRIGGER_THRESHOLD;
+        if let Some(pressed) = trig.left.update(g.bLeftTrigger >= threshold) {
+            let _ = match pressed {
+                true => tx.send(RawEvent::new(
+                    id,
+                    RawEventType::ButtonPressed(native_ev_codes::BTN_LT2),
+                )),
+                false => tx.send(RawEvent::new(
+                    id,
+                    RawEventType::ButtonReleased(native_ev_codes::BTN_LT2),
+                )),
+            };
+        }
+        if let Some(pressed) = trig.right.update(g.bRightTrigger >= threshold) {
+            let _ = match pressed {
+                true => tx.send(RawEvent::new(
+                    id,
+                    RawEventType::ButtonPressed(native_ev_codes::BTN_RT2),
+                )),
+                false => tx.send(RawEvent::new(
+                    id,
+                    RawEventType::ButtonReleased(native_ev_codes::BTN_RT2),
+                )),
+            };
+        }
         if g.sThumbLX != pg.sThumbLX {
             let _ = tx.send(RawEvent::new(
                 id,
@@ -169,6 +322,22 @@ impl Gilrs {
                 RawEventType::AxisValueChanged(g.sThumbRY as i32, native_ev_codes::AXIS_RSTICKY),
             ));
         }
+        Self::compare_stick_dir(
+            id,
+            (g.sThumbLX, g.sThumbLY),
+            (pg.sThumbLX, pg.sThumbLY),
+            XINPUT_GAMEPAD_LEFT_THUMB_DEADZONE as f32,
+            native_ev_codes::AXIS_LSTICKX,
+            tx,
+        );
+        Self::compare_stick_dir(
+            id,
+            (g.sThumbRX, g.sThumbRY),
+            (pg.sThumbRX, pg.sThumbRY),
+            XINPUT_GAMEPAD_RIGHT_THUMB_DEADZONE as f32,
+            native_ev_codes::AXIS_RSTICKX,
+            tx,
+        );
         if !is_mask_eq(g.wButtons, pg.wButtons, XINPUT_GAMEPAD_DPAD_UP) {
             let _ = match g.wButtons & XINPUT_GAMEPAD_DPAD_UP != 0 {
                 true => tx.send(RawEvent::new(
@@ -338,11 +507,176 @@ impl Gilrs {
             };
         }
     }
+
+    /// Emit a derived direction event when a stick's quantized 8-way direction
+    /// changes between two polls.
+    ///
+    /// The raw axis events above are left untouched; this layer is purely
+    /// additive and spares menu / d-pad-emulation consumers from re-implementing
+    /// the deadzone, normalization and angle-quantization maths themselves. The
+    /// model follows the `GAMEPAD_AXIS` struct from the Sean Middleditch gamepad
+    /// library (`nx, ny, length, dirLast, dirCurrent`).
+    fn compare_stick_dir(
+        id: usize,
+        cur: (i16, i16),
+        prev: (i16, i16),
+        deadzone: f32,
+        stick: EvCode,
+        tx: &Sender<RawEvent>,
+    ) {
+        let now = StickState::new(cur.0, cur.1, deadzone).direction;
+        let was = StickState::new(prev.0, prev.1, deadzone).direction;
+
+        if now != was {
+            let _ = tx.send(RawEvent::new(
+                id,
+                RawEventType::StickDirectionChanged(now, stick),
+            ));
+        }
+    }
+}
+
+/// Deadzone-corrected view of a single thumb stick.
+///
+/// `nx`/`ny` is the normalized direction, `length` the magnitude in
+/// `0.0..=1.0`, and `angle` its orientation in radians. `direction` is `angle`
+/// quantized into the eight compass points (or [`StickDirection::Centered`]
+/// while inside the deadzone).
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct StickState {
+    nx: f32,
+    ny: f32,
+    length: f32,
+    angle: f32,
+    direction: StickDirection,
+}
+
+impl StickState {
+    fn new(x: i16, y: i16, deadzone: f32) -> Self {
+        let (fx, fy) = (x as f32, y as f32);
+        let magnitude = (fx * fx + fy * fy).sqrt();
+
+        if magnitude <= deadzone {
+            return StickState {
+                nx: 0.0,
+                ny: 0.0,
+                length: 0.0,
+                angle: 0.0,
+                direction: StickDirection::Centered,
+            };
+        }
+
+        let nx = fx / magnitude;
+        let ny = fy / magnitude;
+        // Rescale the magnitude so it runs 0.0..=1.0 from the deadzone edge to the
+        // axis maximum, as the Middleditch library does.
+        let max = i16::max_value() as f32;
+        let length = ((magnitude.min(max) - deadzone) / (max - deadzone)).min(1.0);
+        let angle = ny.atan2(nx);
+
+        StickState {
+            nx,
+            ny,
+            length,
+            angle,
+            direction: StickDirection::from_angle(angle),
+        }
+    }
+}
+
+/// Eight-way compass direction of a thumb stick, plus a centered state.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum StickDirection {
+    Centered,
+    East,
+    NorthEast,
+    North,
+    NorthWest,
+    West,
+    SouthWest,
+    South,
+    SouthEast,
+}
+
+impl StickDirection {
+    /// Quantize an angle in radians (`atan2(y, x)`, east = 0, CCW positive) into
+    /// the nearest of the eight compass points.
+    fn from_angle(angle: f32) -> Self {
+        use std::f32::consts::PI;
+
+        // Shift by half a sector so each direction spans a 45° window centered on
+        // its axis, then bucket into 0..8.
+        let sector = (angle / (PI / 4.0)).round() as i32 & 7;
+        match sector {
+            0 => StickDirection::East,
+            1 => StickDirection::NorthEast,
+            2 => StickDirection::North,
+            3 => StickDirection::NorthWest,
+            4 => StickDirection::West,
+            5 => StickDirection::SouthWest,
+            6 => StickDirection::South,
+            7 => StickDirection::SouthEast,
+            _ => StickDirection::Centered,
+        }
+    }
+}
+
+/// High-level category of an XInput controller, mapped from the `SubType`
+/// returned by `XInputGetCapabilities`. Modeled on the `GamepadType` enum used
+/// by doukutsu-rs.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ControllerType {
+    Gamepad,
+    Wheel,
+    ArcadeStick,
+    FlightStick,
+    DancePad,
+    Guitar,
+    GuitarAlternate,
+    GuitarBass,
+    DrumKit,
+    ArcadePad,
+    Unknown,
+}
+
+impl ControllerType {
+    fn from_subtype(subtype: u8) -> Self {
+        match subtype {
+            XINPUT_DEVSUBTYPE_GAMEPAD => ControllerType::Gamepad,
+            XINPUT_DEVSUBTYPE_WHEEL => ControllerType::Wheel,
+            XINPUT_DEVSUBTYPE_ARCADE_STICK => ControllerType::ArcadeStick,
+            XINPUT_DEVSUBTYPE_FLIGHT_STICK => ControllerType::FlightStick,
+            XINPUT_DEVSUBTYPE_DANCE_PAD => ControllerType::DancePad,
+            XINPUT_DEVSUBTYPE_GUITAR => ControllerType::Guitar,
+            XINPUT_DEVSUBTYPE_GUITAR_ALTERNATE => ControllerType::GuitarAlternate,
+            XINPUT_DEVSUBTYPE_GUITAR_BASS => ControllerType::GuitarBass,
+            XINPUT_DEVSUBTYPE_DRUM_KIT => ControllerType::DrumKit,
+            XINPUT_DEVSUBTYPE_ARCADE_PAD => ControllerType::ArcadePad,
+            _ => ControllerType::Unknown,
+        }
+    }
+
+    fn as_name(&self) -> &'static str {
+        match *self {
+            ControllerType::Gamepad => "Xbox Controller",
+            ControllerType::Wheel => "Xbox Racing Wheel",
+            ControllerType::ArcadeStick => "Xbox Arcade Stick",
+            ControllerType::FlightStick => "Xbox Flight Stick",
+            ControllerType::DancePad => "Xbox Dance Pad",
+            ControllerType::Guitar => "Xbox Guitar",
+            ControllerType::GuitarAlternate => "Xbox Guitar",
+            ControllerType::GuitarBass => "Xbox Bass Guitar",
+            ControllerType::DrumKit => "Xbox Drum Kit",
+            ControllerType::ArcadePad => "Xbox Arcade Pad",
+            ControllerType::Unknown => "Xbox Controller",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Gamepad {
     uuid: Uuid,
+    name: String,
     id: u32,
 }
 
@@ -350,42 +684,70 @@ impl Gamepad {
     fn none() -> Self {
         Gamepad {
             uuid: Uuid::nil(),
+            name: String::from("Xbox Controller"),
             id: u32::MAX,
         }
     }
 
+    fn from_dinput(pad: &dinput::DiDeviceInfo) -> Self {
+        Gamepad {
+            uuid: pad.uuid,
+            name: pad.name.clone(),
+            id: pad.id,
+        }
+    }
+
     pub fn name(&self) -> &str {
-        "Xbox Controller"
+        // DirectInput pads carry a real product string; XInput pads only report a
+        // subtype, so derive a human-readable name from it.
+        if self.id as usize >= MAX_XINPUT_PADS {
+            &self.name
+        } else {
+            self.controller_type().as_name()
+        }
     }
 
     pub fn uuid(&self) -> Uuid {
         self.uuid
     }
 
+    /// Query the controller subtype reported by `XInputGetCapabilities`.
+    ///
+    /// DirectInput pads are always reported as [`ControllerType::Gamepad`] since
+    /// the capability call only applies to the XInput slots.
+    pub fn controller_type(&self) -> ControllerType {
+        if self.id as usize >= MAX_XINPUT_PADS {
+            return ControllerType::Gamepad;
+        }
+        match self.capabilities() {
+            Some(caps) => ControllerType::from_subtype(caps.SubType),
+            None => ControllerType::Unknown,
+        }
+    }
+
+    fn capabilities(&self) -> Option<xi::XINPUT_CAPABILITIES> {
+        if self.id as usize >= MAX_XINPUT_PADS {
+            return None;
+        }
+        unsafe {
+            let mut caps = mem::zeroed::<xi::XINPUT_CAPABILITIES>();
+            if xinput::XInputGetCapabilities(self.id, XINPUT_FLAG_GAMEPAD, &mut caps)
+                == ERROR_SUCCESS
+            {
+                Some(caps)
+            } else {
+                None
+            }
+        }
+    }
+
     pub fn power_info(&self) -> PowerInfo {
         unsafe {
             let mut binfo = mem::uninitialized::<XBatteryInfo>();
             if xinput::XInputGetBatteryInformation(self.id, xi::BATTERY_DEVTYPE_GAMEPAD, &mut binfo)
                 == ERROR_SUCCESS
             {
-                match binfo.BatteryType {
-                    xi::BATTERY_TYPE_WIRED => PowerInfo::Wired,
-                    xi::BATTERY_TYPE_ALKALINE | xi::BATTERY_TYPE_NIMH => {
-                        let lvl = match binfo.BatteryLevel {
-                            xi::BATTERY_LEVEL_EMPTY => 0,
-                            xi::BATTERY_LEVEL_LOW => 33,
-                            xi::BATTERY_LEVEL_MEDIUM => 67,
-                            xi::BATTERY_LEVEL_FULL => 100,
-                            _ => unreachable!(),
-                        };
-                        if lvl == 100 {
-                            PowerInfo::Charged
-                        } else {
-                            PowerInfo::Discharging(lvl)
-                        }
-                    }
-                    _ => PowerInfo::Unknown,
-                }
+                power_info_from_battery(&binfo)
             } else {
                 PowerInfo::Unknown
             }
@@ -393,7 +755,17 @@ impl Gamepad {
     }
 
     pub fn is_ff_supported(&self) -> bool {
-        true
+        self.capabilities()
+            .map(|caps| caps.Flags & XINPUT_CAPS_FFB_SUPPORTED != 0)
+            .unwrap_or(false)
+    }
+
+    /// Whether the device exposes a headset/voice endpoint, per its XInput
+    /// capability flags.
+    pub fn has_voice(&self) -> bool {
+        self.capabilities()
+            .map(|caps| caps.Flags & XINPUT_CAPS_VOICE_SUPPORTED != 0)
+            .unwrap_or(false)
     }
 
     pub fn ff_device(&self) -> Option<FfDevice> {
@@ -415,14 +787,67 @@ impl Gamepad {
     }
 }
 
+/// Per-gamepad latch for the two analog triggers, mirroring the
+/// `pressedLast`/`pressedCurrent` pair in the Middleditch library.
+#[derive(Copy, Clone, Debug, Default)]
+struct TriggerState {
+    left: TriggerLatch,
+    right: TriggerLatch,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct TriggerLatch {
+    pressed: bool,
+}
+
+impl TriggerLatch {
+    /// Feed the current "above threshold" reading and return `Some(pressed)` only
+    /// on a transition, so callers emit exactly one button event per crossing.
+    fn update(&mut self, pressed: bool) -> Option<bool> {
+        if pressed != self.pressed {
+            self.pressed = pressed;
+            Some(pressed)
+        } else {
+            None
+        }
+    }
+}
+
 #[inline(always)]
 fn is_mask_eq(l: u16, r: u16, mask: u16) -> bool {
     (l & mask != 0) == (r & mask != 0)
 }
 
+/// Translate an `XINPUT_BATTERY_INFORMATION` reading into a [`PowerInfo`]. Shared
+/// by the on-demand `Gamepad::power_info` and the event thread's battery poll.
+fn power_info_from_battery(binfo: &XBatteryInfo) -> PowerInfo {
+    match binfo.BatteryType {
+        xi::BATTERY_TYPE_WIRED => PowerInfo::Wired,
+        xi::BATTERY_TYPE_ALKALINE | xi::BATTERY_TYPE_NIMH => {
+            let lvl = match binfo.BatteryLevel {
+                xi::BATTERY_LEVEL_EMPTY => 0,
+                xi::BATTERY_LEVEL_LOW => 33,
+                xi::BATTERY_LEVEL_MEDIUM => 67,
+                xi::BATTERY_LEVEL_FULL => 100,
+                // This now runs on the event thread's battery poll; a driver
+                // returning an unexpected level must not panic it (and take all
+                // input down with it), so fall back to `Unknown`.
+                _ => return PowerInfo::Unknown,
+            };
+            if lvl == 100 {
+                PowerInfo::Charged
+            } else {
+                PowerInfo::Discharging(lvl)
+            }
+        }
+        _ => PowerInfo::Unknown,
+    }
+}
+
 fn gamepad_new(id: u32) -> gamepad::Gamepad {
     let gamepad = Gamepad {
         uuid: Uuid::nil(),
+        name: String::from("Xbox Controller"),
         id,
     };
 
@@ -447,6 +872,660 @@ impl Display for EvCode {
     }
 }
 
+/// DirectInput fallback backend.
+///
+/// XInput only reports the four Xbox-compatible slots, so DualShock/DualSense
+/// pads and generic USB joysticks are invisible through it. This module
+/// enumerates HID game controllers through `IDirectInput8::EnumDevices`, skips
+/// the ones already owned by an XInput slot (the well-known `&IG_` trick), and
+/// feeds the same [`RawEvent`] channel by diffing `DIJOYSTATE2` snapshots.
+mod dinput {
+    use ev::{RawEvent, RawEventType};
+
+    use uuid::Uuid;
+    use winapi::dinput::{IDirectInput8W, IDirectInputDevice8W, DIDEVICEINSTANCEW, DIJOYSTATE2,
+                         DIPROPGUIDANDPATH, DI8DEVCLASS_GAMECTRL, DIEDFL_ATTACHEDONLY,
+                         DISCL_BACKGROUND, DISCL_NONEXCLUSIVE, c_dfDIJoystick2};
+    use winapi::winerror::{DI_OK, S_OK};
+    use winapi::minwindef::{BOOL, DWORD, LPVOID, TRUE};
+    use winapi::guiddef::GUID;
+
+    use std::{mem, ptr};
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use std::sync::mpsc::Sender;
+
+    use super::{native_ev_codes as nec, MAX_XINPUT_PADS};
+
+    extern "system" {
+        fn DirectInput8Create(
+            inst: ::winapi::minwindef::HINSTANCE,
+            version: DWORD,
+            riid: *const GUID,
+            out: *mut LPVOID,
+            outer: LPVOID,
+        ) -> ::winapi::winerror::HRESULT;
+        fn GetModuleHandleW(name: *const u16) -> ::winapi::minwindef::HINSTANCE;
+    }
+
+    const DIRECTINPUT_VERSION: DWORD = 0x0800;
+    // POV hats report hundredths of a degree, or -1 (0xFFFF_FFFF) when centered.
+    const POV_CENTERED: DWORD = 0xFFFF_FFFF;
+
+    /// Descriptor handed back to `Gilrs::new` so the public `Gamepad` can carry a
+    /// real uuid and product name before the event thread has started polling.
+    #[derive(Clone, Debug)]
+    pub struct DiDeviceInfo {
+        pub id: u32,
+        pub uuid: Uuid,
+        pub name: String,
+        guid: GUID,
+    }
+
+    /// A live DirectInput device, acquired on (and owned by) the event thread.
+    pub struct DiGamepad {
+        id: usize,
+        device: *mut IDirectInputDevice8W,
+        prev: DIJOYSTATE2,
+        // Whether the device is currently acquired. Tracked so a lost device
+        // emits exactly one `Disconnected` (and a `Connected` on re-acquire),
+        // matching the XInput slots.
+        acquired: bool,
+    }
+
+    struct EnumCtx {
+        next_id: u32,
+        devices: Vec<DiDeviceInfo>,
+        di: *mut IDirectInput8W,
+    }
+
+    /// Enumerate every attached HID game controller that is *not* already handled
+    /// by an XInput slot. Ids continue from [`MAX_XINPUT_PADS`].
+    pub fn enumerate() -> Vec<DiDeviceInfo> {
+        unsafe {
+            let di = match create() {
+                Some(di) => di,
+                None => return Vec::new(),
+            };
+
+            let mut ctx = EnumCtx {
+                next_id: MAX_XINPUT_PADS as u32,
+                devices: Vec::new(),
+                di,
+            };
+
+            ((*(*di).lpVtbl).EnumDevices)(
+                di,
+                DI8DEVCLASS_GAMECTRL,
+                Some(enum_callback),
+                &mut ctx as *mut _ as LPVOID,
+                DIEDFL_ATTACHEDONLY,
+            );
+
+            ((*(*di).lpVtbl).Release)(di);
+            ctx.devices
+        }
+    }
+
+    /// Re-open the already-enumerated devices as live COM objects on the calling
+    /// thread. The `infos` are the exact descriptors `Gilrs::new` assigned ids to,
+    /// so a device's live poll events land on the same `gamepads[]` index even if
+    /// the attachment order changes after construction.
+    pub fn acquire(infos: Vec<DiDeviceInfo>) -> Vec<DiGamepad> {
+        unsafe {
+            let di = match create() {
+                Some(di) => di,
+                None => return Vec::new(),
+            };
+
+            let mut pads = Vec::new();
+            for info in infos {
+                let mut device: *mut IDirectInputDevice8W = ptr::null_mut();
+                if ((*(*di).lpVtbl).CreateDevice)(di, &info.guid, &mut device, ptr::null_mut())
+                    != DI_OK
+                {
+                    continue;
+                }
+
+                ((*(*device).lpVtbl).SetDataFormat)(device, &c_dfDIJoystick2);
+                ((*(*device).lpVtbl).SetCooperativeLevel)(
+                    device,
+                    ptr::null_mut(),
+                    DISCL_BACKGROUND | DISCL_NONEXCLUSIVE,
+                );
+
+                // DirectInput axes default to an unsigned `0..=65535` range. Set
+                // every axis to report the signed `i16` range the XInput path (and
+                // `AXES_INFO`) expects, so a centered stick reads ~0 rather than
+                // ~+100%.
+                let mut range: ::winapi::dinput::DIPROPRANGE = mem::zeroed();
+                range.diph.dwSize = mem::size_of::<::winapi::dinput::DIPROPRANGE>() as DWORD;
+                range.diph.dwHeaderSize =
+                    mem::size_of::<::winapi::dinput::DIPROPHEADER>() as DWORD;
+                range.diph.dwHow = ::winapi::dinput::DIPH_DEVICE;
+                range.diph.dwObj = 0;
+                range.lMin = i32::from(::std::i16::MIN);
+                range.lMax = i32::from(::std::i16::MAX);
+                ((*(*device).lpVtbl).SetProperty)(
+                    device,
+                    ::winapi::dinput::DIPROP_RANGE,
+                    &range.diph,
+                );
+
+                // Record whether the initial acquire actually succeeded, so a
+                // device that is momentarily busy at startup does not immediately
+                // flap Disconnected→Connected on its first poll.
+                let acquired = ((*(*device).lpVtbl).Acquire)(device) >= 0;
+
+                pads.push(DiGamepad {
+                    id: info.id as usize,
+                    device,
+                    prev: mem::zeroed(),
+                    acquired,
+                });
+            }
+
+            ((*(*di).lpVtbl).Release)(di);
+            pads
+        }
+    }
+
+    unsafe fn create() -> Option<*mut IDirectInput8W> {
+        let mut di: *mut IDirectInput8W = ptr::null_mut();
+        let hr = DirectInput8Create(
+            GetModuleHandleW(ptr::null()),
+            DIRECTINPUT_VERSION,
+            &::winapi::dinput::IID_IDirectInput8W,
+            &mut di as *mut _ as *mut LPVOID,
+            ptr::null_mut(),
+        );
+        if hr == S_OK && !di.is_null() {
+            Some(di)
+        } else {
+            None
+        }
+    }
+
+    unsafe extern "system" fn enum_callback(
+        inst: *const DIDEVICEINSTANCEW,
+        data: LPVOID,
+    ) -> BOOL {
+        let ctx = &mut *(data as *mut EnumCtx);
+        let inst = &*inst;
+
+        // Pads reachable through XInput are reported twice; the canonical way to
+        // spot them is the `IG_` marker in the device interface path. The path is
+        // queried from the *instance* the enumeration just handed us.
+        if is_xinput_device(ctx.di, &inst.guidInstance) {
+            return TRUE;
+        }
+
+        let id = ctx.next_id;
+        ctx.next_id += 1;
+        ctx.devices.push(DiDeviceInfo {
+            id,
+            uuid: uuid_from_guid(&inst.guidProduct),
+            name: wide_to_string(&inst.tszProductName),
+            guid: inst.guidInstance,
+        });
+
+        TRUE
+    }
+
+    /// Derive a stable `Uuid` from a device's VID/PID so applications can tell
+    /// pads apart and look up mappings, instead of the old `Uuid::nil()`.
+    ///
+    /// DirectInput packs the USB VID/PID into the first four bytes of
+    /// `guidProduct`; we lay them out in the same byte positions the SDL mapping
+    /// database keys its entries on. The HID version is not carried in
+    /// `guidProduct`, so its slot is left zero.
+    fn uuid_from_guid(guid: &GUID) -> Uuid {
+        let vid = (guid.Data1 & 0xFFFF) as u16;
+        let pid = (guid.Data1 >> 16) as u16;
+        let bytes = [
+            0x03, 0x00, 0x00, 0x00,
+            (vid & 0xFF) as u8, (vid >> 8) as u8, 0x00, 0x00,
+            (pid & 0xFF) as u8, (pid >> 8) as u8, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        Uuid::from_bytes(&bytes).unwrap_or_else(|_| Uuid::nil())
+    }
+
+    /// Return `true` when the device is already serviced by an XInput slot. The
+    /// check matches the `&IG_` substring in the device interface path, as used
+    /// by ebiten and the Sean Middleditch gamepad library.
+    fn is_xinput_device(di: *mut IDirectInput8W, instance: &GUID) -> bool {
+        unsafe {
+            let mut dev: *mut IDirectInputDevice8W = ptr::null_mut();
+            if ((*(*di).lpVtbl).CreateDevice)(di, instance, &mut dev, ptr::null_mut()) != DI_OK {
+                return false;
+            }
+
+            let mut prop: DIPROPGUIDANDPATH = mem::zeroed();
+            prop.diph.dwSize = mem::size_of::<DIPROPGUIDANDPATH>() as DWORD;
+            prop.diph.dwHeaderSize = mem::size_of::<::winapi::dinput::DIPROPHEADER>() as DWORD;
+            prop.diph.dwHow = ::winapi::dinput::DIPH_DEVICE;
+
+            let found = if ((*(*dev).lpVtbl).GetProperty)(
+                dev,
+                ::winapi::dinput::DIPROP_GUIDANDPATH,
+                &mut prop.diph,
+            ) == S_OK
+            {
+                let path = wide_to_string(&prop.wszPath);
+                path.contains("&ig_") || path.contains("&IG_")
+            } else {
+                false
+            };
+
+            ((*(*dev).lpVtbl).Release)(dev);
+            found
+        }
+    }
+
+    fn wide_to_string(wide: &[u16]) -> String {
+        let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+        OsString::from_wide(&wide[..len])
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    impl DiGamepad {
+        /// Poll the device once and diff it against the previous snapshot, feeding
+        /// the same axis/button events the XInput path produces.
+        pub fn poll(&mut self, tx: &Sender<RawEvent>) {
+            unsafe {
+                ((*(*self.device).lpVtbl).Poll)(self.device);
+
+                let mut state: DIJOYSTATE2 = mem::zeroed();
+                if ((*(*self.device).lpVtbl).GetDeviceState)(
+                    self.device,
+                    mem::size_of::<DIJOYSTATE2>() as DWORD,
+                    &mut state as *mut _ as LPVOID,
+                ) != S_OK
+                {
+                    // Device was lost (unplugged, or the initial acquire never
+                    // took). Report the disconnect once, then keep trying to
+                    // re-acquire on later ticks in case it comes back on the same
+                    // handle.
+                    if self.acquired {
+                        self.acquired = false;
+                        self.prev = mem::zeroed();
+                        let _ = tx.send(RawEvent::new(self.id, RawEventType::Disconnected));
+                    }
+                    ((*(*self.device).lpVtbl).Acquire)(self.device);
+                    return;
+                }
+
+                // A successful read after a loss means the device came back on
+                // the same handle.
+                if !self.acquired {
+                    self.acquired = true;
+                    let _ = tx.send(RawEvent::new(self.id, RawEventType::Connected));
+                }
+
+                self.compare_state(&state, tx);
+                self.prev = state;
+            }
+        }
+
+        fn compare_state(&self, s: &DIJOYSTATE2, tx: &Sender<RawEvent>) {
+            let p = &self.prev;
+            let id = self.id;
+
+            if s.lX != p.lX {
+                let _ = tx.send(RawEvent::new(
+                    id,
+                    RawEventType::AxisValueChanged(s.lX, nec::AXIS_LSTICKX),
+                ));
+            }
+            if s.lY != p.lY {
+                // DirectInput reports Y positive-down; XInput (and `AXIS_LSTICKY`)
+                // is positive-up, so negate to keep both backends consistent.
+                let _ = tx.send(RawEvent::new(
+                    id,
+                    RawEventType::AxisValueChanged(-s.lY, nec::AXIS_LSTICKY),
+                ));
+            }
+            if s.lRx != p.lRx {
+                let _ = tx.send(RawEvent::new(
+                    id,
+                    RawEventType::AxisValueChanged(s.lRx, nec::AXIS_RSTICKX),
+                ));
+            }
+            if s.lRy != p.lRy {
+                let _ = tx.send(RawEvent::new(
+                    id,
+                    RawEventType::AxisValueChanged(-s.lRy, nec::AXIS_RSTICKY),
+                ));
+            }
+
+            for (i, (&now, &was)) in s.rgbButtons.iter().zip(p.rgbButtons.iter()).enumerate() {
+                if (now & 0x80) != (was & 0x80) {
+                    let code = nec::button_from_index(i);
+                    let _ = if now & 0x80 != 0 {
+                        tx.send(RawEvent::new(id, RawEventType::ButtonPressed(code)))
+                    } else {
+                        tx.send(RawEvent::new(id, RawEventType::ButtonReleased(code)))
+                    };
+                }
+            }
+
+            self.compare_pov(s.rgdwPOV[0], p.rgdwPOV[0], tx);
+        }
+
+        /// Translate the first POV hat into the four d-pad buttons.
+        fn compare_pov(&self, now: DWORD, was: DWORD, tx: &Sender<RawEvent>) {
+            if now == was {
+                return;
+            }
+
+            let decode = |pov: DWORD| -> (bool, bool, bool, bool) {
+                if pov == POV_CENTERED {
+                    return (false, false, false, false);
+                }
+                let deg = pov / 100;
+                (
+                    deg >= 315 || deg <= 45,             // up
+                    deg >= 135 && deg <= 225,            // down
+                    deg >= 225 && deg <= 315,            // left
+                    deg >= 45 && deg <= 135,             // right
+                )
+            };
+
+            let (un, dn, ln, rn) = decode(now);
+            let (uw, dw, lw, rw) = decode(was);
+            let id = self.id;
+            let mut emit = |changed: bool, pressed: bool, code| {
+                if changed {
+                    let _ = if pressed {
+                        tx.send(RawEvent::new(id, RawEventType::ButtonPressed(code)))
+                    } else {
+                        tx.send(RawEvent::new(id, RawEventType::ButtonReleased(code)))
+                    };
+                }
+            };
+            emit(un != uw, un, nec::BTN_DPAD_UP);
+            emit(dn != dw, dn, nec::BTN_DPAD_DOWN);
+            emit(ln != lw, ln, nec::BTN_DPAD_LEFT);
+            emit(rn != rw, rn, nec::BTN_DPAD_RIGHT);
+        }
+    }
+
+    impl Drop for DiGamepad {
+        fn drop(&mut self) {
+            unsafe {
+                if !self.device.is_null() {
+                    ((*(*self.device).lpVtbl).Unacquire)(self.device);
+                    ((*(*self.device).lpVtbl).Release)(self.device);
+                }
+            }
+        }
+    }
+
+    // The COM handle is only ever touched on the event thread that created it, so
+    // it is safe to move the wrapper onto that thread.
+    unsafe impl Send for DiGamepad {}
+}
+
+/// Event-driven connect/disconnect through `Windows.Gaming.Input`.
+///
+/// On Windows 10+ the `RawGameController` class raises `Added`/`Removed` events
+/// the instant a controller appears or vanishes. We subscribe to those and flip
+/// a shared flag so the poll loop rescans on its next tick, rather than waiting
+/// out its 100-iteration rescan cadence. On older systems the activation
+/// factory is unavailable and [`register`] returns `None`, leaving the loop on
+/// its polling fallback. The approach mirrors the move ebiten's desktop backend
+/// made toward WGI notifications.
+mod wgi {
+    use winapi::guiddef::{GUID, IID};
+    use winapi::minwindef::{LPVOID, ULONG};
+    use winapi::winerror::{E_NOINTERFACE, HRESULT, S_OK};
+    use winapi::winnt::HSTRING;
+
+    use std::ptr;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    extern "system" {
+        fn RoGetActivationFactory(
+            class_id: HSTRING,
+            iid: *const IID,
+            factory: *mut LPVOID,
+        ) -> HRESULT;
+        fn WindowsCreateString(
+            src: *const u16,
+            len: u32,
+            out: *mut HSTRING,
+        ) -> HRESULT;
+        fn WindowsDeleteString(string: HSTRING) -> HRESULT;
+        fn CoInitializeEx(reserved: LPVOID, co_init: u32) -> HRESULT;
+    }
+
+    // Initialize the calling thread as a multithreaded apartment. WGI dispatches
+    // the Added/Removed events free-threaded, which suits the MTA.
+    const COINIT_MULTITHREADED: u32 = 0x0;
+
+    // IRawGameControllerStatics, as defined by the Windows SDK headers.
+    const IID_IRAW_GAME_CONTROLLER_STATICS: IID = GUID {
+        Data1: 0xEB8D0792,
+        Data2: 0xE95A,
+        Data3: 0x4B19,
+        Data4: [0xAF, 0xC7, 0x0A, 0x59, 0xF8, 0xBF, 0x75, 0x9E],
+    };
+
+    const RUNTIME_CLASS: &str = "Windows.Gaming.Input.RawGameController";
+
+    // The only interfaces our delegate answers to: IUnknown and IAgileObject (the
+    // latter lets the runtime call us free-threaded, which is exactly how the
+    // Added/Removed events are dispatched).
+    const IID_IUNKNOWN: IID = GUID {
+        Data1: 0x0000_0000,
+        Data2: 0x0000,
+        Data3: 0x0000,
+        Data4: [0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46],
+    };
+    const IID_IAGILE_OBJECT: IID = GUID {
+        Data1: 0x94EA_2B94,
+        Data2: 0xE9CC,
+        Data3: 0x49E0,
+        Data4: [0xC0, 0xFF, 0xEE, 0x64, 0xCA, 0x8F, 0x5B, 0x90],
+    };
+
+    fn guid_eq(a: &GUID, b: &GUID) -> bool {
+        a.Data1 == b.Data1 && a.Data2 == b.Data2 && a.Data3 == b.Data3 && a.Data4 == b.Data4
+    }
+
+    /// Minimal slice of `IRawGameControllerStatics` we need: the two event
+    /// registration/removal methods. Laid out after the three `IInspectable`
+    /// slots that follow `IUnknown`.
+    #[repr(C)]
+    struct StaticsVtbl {
+        query_interface: usize,
+        add_ref: usize,
+        release: unsafe extern "system" fn(*mut Statics) -> ULONG,
+        get_iids: usize,
+        get_runtime_class_name: usize,
+        get_trust_level: usize,
+        add_controller_added: unsafe extern "system" fn(
+            *mut Statics,
+            *mut Handler,
+            *mut i64,
+        ) -> HRESULT,
+        remove_controller_added: unsafe extern "system" fn(*mut Statics, i64) -> HRESULT,
+        add_controller_removed: unsafe extern "system" fn(
+            *mut Statics,
+            *mut Handler,
+            *mut i64,
+        ) -> HRESULT,
+        remove_controller_removed: unsafe extern "system" fn(*mut Statics, i64) -> HRESULT,
+    }
+
+    #[repr(C)]
+    struct Statics {
+        vtbl: *const StaticsVtbl,
+    }
+
+    /// COM object implementing `EventHandler<RawGameController>`; its only job is
+    /// to flip the shared flag when invoked.
+    #[repr(C)]
+    struct Handler {
+        vtbl: *const HandlerVtbl,
+        refs: ULONG,
+        flag: Arc<AtomicBool>,
+    }
+
+    #[repr(C)]
+    struct HandlerVtbl {
+        query_interface: unsafe extern "system" fn(*mut Handler, *const IID, *mut LPVOID) -> HRESULT,
+        add_ref: unsafe extern "system" fn(*mut Handler) -> ULONG,
+        release: unsafe extern "system" fn(*mut Handler) -> ULONG,
+        invoke: unsafe extern "system" fn(*mut Handler, LPVOID, LPVOID) -> HRESULT,
+    }
+
+    static HANDLER_VTBL: HandlerVtbl = HandlerVtbl {
+        query_interface: handler_query_interface,
+        add_ref: handler_add_ref,
+        release: handler_release,
+        invoke: handler_invoke,
+    };
+
+    unsafe extern "system" fn handler_query_interface(
+        this: *mut Handler,
+        iid: *const IID,
+        out: *mut LPVOID,
+    ) -> HRESULT {
+        // Only hand out `this` for `IUnknown` and `IAgileObject`; refusing
+        // everything else is what lets the runtime probe us correctly instead of
+        // treating an arbitrary interface request as satisfied.
+        if guid_eq(&*iid, &IID_IUNKNOWN) || guid_eq(&*iid, &IID_IAGILE_OBJECT) {
+            *out = this as LPVOID;
+            (*this).refs += 1;
+            S_OK
+        } else {
+            *out = ptr::null_mut();
+            E_NOINTERFACE
+        }
+    }
+
+    unsafe extern "system" fn handler_add_ref(this: *mut Handler) -> ULONG {
+        (*this).refs += 1;
+        (*this).refs
+    }
+
+    unsafe extern "system" fn handler_release(this: *mut Handler) -> ULONG {
+        (*this).refs -= 1;
+        let refs = (*this).refs;
+        if refs == 0 {
+            drop(Box::from_raw(this));
+        }
+        refs
+    }
+
+    unsafe extern "system" fn handler_invoke(
+        this: *mut Handler,
+        _sender: LPVOID,
+        _args: LPVOID,
+    ) -> HRESULT {
+        (*this).flag.store(true, Ordering::Relaxed);
+        S_OK
+    }
+
+    fn new_handler(flag: Arc<AtomicBool>) -> *mut Handler {
+        Box::into_raw(Box::new(Handler {
+            vtbl: &HANDLER_VTBL,
+            refs: 1,
+            flag,
+        }))
+    }
+
+    /// Live subscription to the `Added`/`Removed` events. Dropping it removes the
+    /// handlers and releases the statics factory.
+    pub struct Registration {
+        statics: *mut Statics,
+        added_token: i64,
+        removed_token: i64,
+    }
+
+    impl Drop for Registration {
+        fn drop(&mut self) {
+            unsafe {
+                let vtbl = &*(*self.statics).vtbl;
+                (vtbl.remove_controller_added)(self.statics, self.added_token);
+                (vtbl.remove_controller_removed)(self.statics, self.removed_token);
+                (vtbl.release)(self.statics);
+            }
+        }
+    }
+
+    // The factory and handlers only ever touch the event thread that registered
+    // them, so the registration is safe to hold there.
+    unsafe impl Send for Registration {}
+
+    /// Subscribe to connect/disconnect notifications, flipping `flag` whenever one
+    /// fires. Returns `None` when `Windows.Gaming.Input` is unavailable.
+    pub fn register(flag: Arc<AtomicBool>) -> Option<Registration> {
+        unsafe {
+            // `RoGetActivationFactory` needs an initialized apartment on the
+            // calling thread; without this it returns `CO_E_NOTINITIALIZED` and
+            // the event-driven path silently never activates. `S_FALSE`
+            // (already initialized) is fine to proceed on, so the return is not
+            // checked — the factory lookup below is the real gate.
+            CoInitializeEx(ptr::null_mut(), COINIT_MULTITHREADED);
+
+            let statics = activation_factory()?;
+            let vtbl = &*(*statics).vtbl;
+
+            let mut added_token = 0i64;
+            let mut removed_token = 0i64;
+
+            let added_ok = (vtbl.add_controller_added)(
+                statics,
+                new_handler(flag.clone()),
+                &mut added_token,
+            ) == S_OK;
+            let removed_ok = (vtbl.add_controller_removed)(
+                statics,
+                new_handler(flag),
+                &mut removed_token,
+            ) == S_OK;
+
+            if added_ok && removed_ok {
+                Some(Registration {
+                    statics,
+                    added_token,
+                    removed_token,
+                })
+            } else {
+                (vtbl.release)(statics);
+                None
+            }
+        }
+    }
+
+    unsafe fn activation_factory() -> Option<*mut Statics> {
+        let wide: Vec<u16> = RUNTIME_CLASS.encode_utf16().collect();
+        let mut class_id: HSTRING = ptr::null_mut();
+        if WindowsCreateString(wide.as_ptr(), wide.len() as u32, &mut class_id) != S_OK {
+            return None;
+        }
+
+        let mut factory: *mut Statics = ptr::null_mut();
+        let hr = RoGetActivationFactory(
+            class_id,
+            &IID_IRAW_GAME_CONTROLLER_STATICS,
+            &mut factory as *mut _ as *mut LPVOID,
+        );
+        WindowsDeleteString(class_id);
+
+        if hr == S_OK && !factory.is_null() {
+            Some(factory)
+        } else {
+            None
+        }
+    }
+}
+
 pub mod native_ev_codes {
     use std::i16::{MAX as I16_MAX, MIN as I16_MIN};
     use std::u8::{MAX as U8_MAX, MIN as U8_MIN};
@@ -518,6 +1597,33 @@ pub mod native_ev_codes {
         AXIS_LT2,
     ];
 
+    // DirectInput reports buttons as a flat array; map indices in the order
+    // A, B, X, Y, LB, RB, Back, Start, Guide, LS, RS, then the d-pad, onto our
+    // native codes so generic pads feel like the XInput ones.
+    static DINPUT_BUTTONS: [EvCode; 15] = [
+        BTN_SOUTH,
+        BTN_EAST,
+        BTN_WEST,
+        BTN_NORTH,
+        BTN_LT,
+        BTN_RT,
+        BTN_SELECT,
+        BTN_START,
+        BTN_MODE,
+        BTN_LTHUMB,
+        BTN_RTHUMB,
+        BTN_DPAD_UP,
+        BTN_DPAD_DOWN,
+        BTN_DPAD_LEFT,
+        BTN_DPAD_RIGHT,
+    ];
+
+    /// Map a DirectInput button index onto a native code, falling back to the
+    /// auxiliary `BTN_C` for anything past the mapped range.
+    pub(super) fn button_from_index(i: usize) -> EvCode {
+        DINPUT_BUTTONS.get(i).cloned().unwrap_or(BTN_C)
+    }
+
     pub(super) static AXES_INFO: [Option<AxisInfo>; 12] = [
         // LeftStickX
         Some(AxisInfo {